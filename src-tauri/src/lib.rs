@@ -1,12 +1,13 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Cursor, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::Emitter;
 use tauri::Manager;
 use zip::ZipArchive;
@@ -35,6 +36,10 @@ struct ConvertPreview {
 struct ConvertResult {
     output_path: String,
     exit_code: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    manifest_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    segments: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,11 +48,154 @@ struct CancelResult {
     requested: bool,
 }
 
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConvertProgress {
+    out_time_sec: f32,
+    frame: u64,
+    fps: f32,
+    speed: f32,
+    percent: f32,
+}
+
+#[derive(Debug, Default)]
+struct ProgressAccumulator {
+    out_time_sec: f32,
+    frame: u64,
+    fps: f32,
+    speed: f32,
+}
+
+impl ProgressAccumulator {
+    fn apply_line(&mut self, line: &str) -> Option<()> {
+        let (key, value) = line.split_once('=')?;
+        match key.trim() {
+            "out_time_ms" => {
+                self.out_time_sec = value.trim().parse::<f64>().ok()? as f32 / 1_000_000.0;
+            }
+            "out_time" => {
+                self.out_time_sec = parse_ffmpeg_timestamp(value.trim()).unwrap_or(self.out_time_sec);
+            }
+            "frame" => {
+                self.frame = value.trim().parse::<u64>().unwrap_or(self.frame);
+            }
+            "fps" => {
+                self.fps = value.trim().parse::<f32>().unwrap_or(self.fps);
+            }
+            "speed" => {
+                self.speed = value
+                    .trim()
+                    .trim_end_matches('x')
+                    .parse::<f32>()
+                    .unwrap_or(self.speed);
+            }
+            "bitrate" | "total_size" | "out_time_us" | "dup_frames" | "drop_frames" | "stream_0_0_q" => {}
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn to_progress(&self, duration_sec: f32) -> ConvertProgress {
+        let percent = if duration_sec > 0.0 {
+            (self.out_time_sec / duration_sec * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        ConvertProgress {
+            out_time_sec: self.out_time_sec,
+            frame: self.frame,
+            fps: self.fps,
+            speed: self.speed,
+            percent,
+        }
+    }
+}
+
+fn parse_ffmpeg_timestamp(value: &str) -> Option<f32> {
+    let mut parts = value.split(':');
+    let hours = parts.next()?.parse::<f32>().ok()?;
+    let minutes = parts.next()?.parse::<f32>().ok()?;
+    let seconds = parts.next()?.parse::<f32>().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
 struct AppState {
-    current_pid: Mutex<Option<u32>>,
+    current_pid: Mutex<Vec<u32>>,
     cancel_requested: AtomicBool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum VideoCodec {
+    H264,
+    H265,
+    Vp9,
+    Av1,
+}
+
+impl VideoCodec {
+    fn encoder_name(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::H265 => "libx265",
+            VideoCodec::Vp9 => "libvpx-vp9",
+            VideoCodec::Av1 => "libsvtav1",
+        }
+    }
+
+    /// VMAFプローブの探索に使うCRF範囲。エンコーダごとにCRFスケールが異なるため、
+    /// target_vmafの探索はこの範囲の両端でプローブし、その間を直線補間して進める。
+    fn vmaf_probe_crf_range(&self) -> (u8, u8) {
+        match self {
+            VideoCodec::H264 => (16, 40),
+            VideoCodec::H265 => (18, 42),
+            VideoCodec::Vp9 => (15, 50),
+            VideoCodec::Av1 => (20, 55),
+        }
+    }
+
+    fn rate_control_args(&self, quality_mode: &str, crf: u8, video_bitrate_k: u32) -> Vec<String> {
+        if quality_mode == "bitrate" {
+            return vec!["-b:v".to_string(), format!("{video_bitrate_k}k")];
+        }
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => vec!["-crf".to_string(), crf.to_string()],
+            VideoCodec::Vp9 => vec![
+                "-crf".to_string(),
+                crf.to_string(),
+                "-b:v".to_string(),
+                "0".to_string(),
+            ],
+            VideoCodec::Av1 => vec![
+                "-crf".to_string(),
+                crf.to_string(),
+                "-preset".to_string(),
+                SVT_AV1_DEFAULT_PRESET.to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AudioCodec {
+    Aac,
+    Opus,
+    Mp3,
+}
+
+impl AudioCodec {
+    fn encoder_name(&self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "aac",
+            AudioCodec::Opus => "libopus",
+            AudioCodec::Mp3 => "libmp3lame",
+        }
+    }
+}
+
+const SVT_AV1_DEFAULT_PRESET: u8 = 8;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ConvertOptions {
@@ -56,10 +204,210 @@ struct ConvertOptions {
     video_bitrate_k: u32,
     fps_mode: String,
     frame_rate: f32,
-    audio_format: String,
+    video_codec: VideoCodec,
+    audio_codec: AudioCodec,
     audio_bitrate_k: u32,
     crf: u8,
     output_ext: String,
+    #[serde(default = "default_quality_mode")]
+    quality_mode: String,
+    #[serde(default)]
+    target_vmaf: f32,
+    #[serde(default = "default_output_kind")]
+    output_kind: String,
+}
+
+fn default_quality_mode() -> String {
+    "crf".to_string()
+}
+
+fn default_output_kind() -> String {
+    "file".to_string()
+}
+
+const HLS_SEGMENT_SEC: u32 = 6;
+const DASH_SEGMENT_SEC: u32 = 6;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InputLimits {
+    #[serde(default = "default_max_duration_sec")]
+    max_duration_sec: f32,
+    #[serde(default = "default_max_width")]
+    max_width: u32,
+    #[serde(default = "default_max_height")]
+    max_height: u32,
+    #[serde(default = "default_max_file_size_bytes")]
+    max_file_size_bytes: u64,
+    #[serde(default = "default_allowed_formats")]
+    allowed_formats: Vec<String>,
+    #[serde(default = "default_allowed_video_codecs")]
+    allowed_video_codecs: Vec<String>,
+    #[serde(default = "default_allowed_audio_codecs")]
+    allowed_audio_codecs: Vec<String>,
+}
+
+fn default_max_duration_sec() -> f32 {
+    4.0 * 3600.0
+}
+
+fn default_max_width() -> u32 {
+    7680
+}
+
+fn default_max_height() -> u32 {
+    4320
+}
+
+fn default_max_file_size_bytes() -> u64 {
+    10 * 1024 * 1024 * 1024
+}
+
+fn default_allowed_formats() -> Vec<String> {
+    vec![
+        "mov,mp4,m4a,3gp,3g2,mj2".to_string(),
+        "matroska,webm".to_string(),
+        "avi".to_string(),
+    ]
+}
+
+fn default_allowed_video_codecs() -> Vec<String> {
+    vec![
+        "H.264".to_string(),
+        "HEVC".to_string(),
+        "VP9".to_string(),
+        "AV1".to_string(),
+        "MPEG-4".to_string(),
+        "VP8".to_string(),
+    ]
+}
+
+fn default_allowed_audio_codecs() -> Vec<String> {
+    vec![
+        "AAC".to_string(),
+        "MP3".to_string(),
+        "Opus".to_string(),
+        "Vorbis".to_string(),
+        "FLAC".to_string(),
+        "PCM".to_string(),
+        "AC-3".to_string(),
+    ]
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        InputLimits {
+            max_duration_sec: default_max_duration_sec(),
+            max_width: default_max_width(),
+            max_height: default_max_height(),
+            max_file_size_bytes: default_max_file_size_bytes(),
+            allowed_formats: default_allowed_formats(),
+            allowed_video_codecs: default_allowed_video_codecs(),
+            allowed_audio_codecs: default_allowed_audio_codecs(),
+        }
+    }
+}
+
+fn codec_allowed(codec_long_name: &str, allowed: &[String]) -> bool {
+    allowed
+        .iter()
+        .any(|candidate| codec_long_name.to_lowercase().contains(&candidate.to_lowercase()))
+}
+
+fn check_input_limits(probe: &ProbeResult, file_size_bytes: u64, limits: &InputLimits) -> Result<(), String> {
+    if !limits
+        .allowed_formats
+        .iter()
+        .any(|allowed| probe.format_name.contains(allowed.as_str()))
+    {
+        return Err(format!(
+            "ERR_INPUT_UNSUPPORTED: 未対応の入力コンテナです: {}",
+            probe.format_name
+        ));
+    }
+    if !probe.video_codec_long_name.is_empty()
+        && !codec_allowed(&probe.video_codec_long_name, &limits.allowed_video_codecs)
+    {
+        return Err(format!(
+            "ERR_INPUT_UNSUPPORTED: 未対応の映像コーデックです: {}",
+            probe.video_codec_long_name
+        ));
+    }
+    if !probe.audio_codec_long_name.is_empty()
+        && !codec_allowed(&probe.audio_codec_long_name, &limits.allowed_audio_codecs)
+    {
+        return Err(format!(
+            "ERR_INPUT_UNSUPPORTED: 未対応の音声コーデックです: {}",
+            probe.audio_codec_long_name
+        ));
+    }
+    if probe.duration_sec > limits.max_duration_sec {
+        return Err(format!(
+            "ERR_INPUT_UNSUPPORTED: 入力の長さが上限を超えています: {}秒 (上限 {}秒)",
+            probe.duration_sec, limits.max_duration_sec
+        ));
+    }
+    if probe.width > limits.max_width || probe.height > limits.max_height {
+        return Err(format!(
+            "ERR_INPUT_UNSUPPORTED: 入力の解像度が上限を超えています: {}x{} (上限 {}x{})",
+            probe.width, probe.height, limits.max_width, limits.max_height
+        ));
+    }
+    if file_size_bytes > limits.max_file_size_bytes {
+        return Err(format!(
+            "ERR_INPUT_TOO_LARGE: 入力ファイルサイズが上限を超えています: {}バイト (上限 {}バイト)",
+            file_size_bytes, limits.max_file_size_bytes
+        ));
+    }
+    Ok(())
+}
+
+fn validate_codec_container(
+    output_ext: &str,
+    video_codec: VideoCodec,
+    audio_codec: AudioCodec,
+) -> Result<Option<String>, String> {
+    match output_ext.to_lowercase().as_str() {
+        "avi" => {
+            if video_codec != VideoCodec::H264 {
+                return Err(format!(
+                    "ERR_CODEC: avi コンテナは {:?} をサポートしません",
+                    video_codec
+                ));
+            }
+            if audio_codec != AudioCodec::Mp3 {
+                return Err(format!(
+                    "ERR_CODEC: avi コンテナは {:?} をサポートしません",
+                    audio_codec
+                ));
+            }
+            Ok(None)
+        }
+        "webm" => {
+            if !matches!(video_codec, VideoCodec::Vp9 | VideoCodec::Av1) {
+                return Err(format!(
+                    "ERR_CODEC: webm コンテナは {:?} をサポートしません",
+                    video_codec
+                ));
+            }
+            if audio_codec != AudioCodec::Opus {
+                return Err(format!(
+                    "ERR_CODEC: webm コンテナは {:?} をサポートしません",
+                    audio_codec
+                ));
+            }
+            Ok(None)
+        }
+        "mp4" | "mov" => {
+            if audio_codec == AudioCodec::Opus {
+                return Ok(Some(
+                    "mp4/mov における opus 音声は再生環境によっては非対応です".to_string(),
+                ));
+            }
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -72,8 +420,83 @@ struct ProbeResult {
     audio_bitrate_k: u32,
     audio_format: String,
     duration_sec: f32,
+    format_name: String,
+    video_codec_long_name: String,
+    audio_codec_long_name: String,
+    pixel_format: String,
+    has_alpha: bool,
+    frame_count: u64,
+    color_primaries: String,
+    color_transfer: String,
+    color_space: String,
+    hdr: bool,
+    mastering_display: Option<String>,
+    content_light_level: Option<String>,
 }
 
+const HDR_TRANSFER_CHARACTERISTICS: &[&str] = &["smpte2084", "arib-std-b67"];
+
+#[derive(Debug, Clone)]
+struct HdrMetadata {
+    color_primaries: String,
+    color_transfer: String,
+    color_space: String,
+    mastering_display: Option<String>,
+    content_light_level: Option<String>,
+}
+
+impl HdrMetadata {
+    fn from_probe(probe: &ProbeResult) -> Option<HdrMetadata> {
+        if !probe.hdr {
+            return None;
+        }
+        Some(HdrMetadata {
+            color_primaries: probe.color_primaries.clone(),
+            color_transfer: probe.color_transfer.clone(),
+            color_space: probe.color_space.clone(),
+            mastering_display: probe.mastering_display.clone(),
+            content_light_level: probe.content_light_level.clone(),
+        })
+    }
+}
+
+fn hdr_encoder_params(video_codec: VideoCodec, hdr: &HdrMetadata) -> Option<(String, String)> {
+    match video_codec {
+        VideoCodec::H265 => {
+            let mut params = vec!["hdr-opt=1".to_string()];
+            if let Some(mastering_display) = &hdr.mastering_display {
+                params.push(format!("master-display={mastering_display}"));
+            }
+            if let Some(content_light_level) = &hdr.content_light_level {
+                params.push(format!("max-cll={content_light_level}"));
+            }
+            Some(("-x265-params".to_string(), params.join(":")))
+        }
+        VideoCodec::Av1 => {
+            let mut params = vec!["enable-hdr=1".to_string()];
+            if let Some(mastering_display) = &hdr.mastering_display {
+                params.push(format!("mastering-display={mastering_display}"));
+            }
+            if let Some(content_light_level) = &hdr.content_light_level {
+                params.push(format!("content-light={content_light_level}"));
+            }
+            Some(("-svtav1-params".to_string(), params.join(":")))
+        }
+        _ => None,
+    }
+}
+
+const ALPHA_PIXEL_FORMATS: &[&str] = &[
+    "yuva420p",
+    "yuva422p",
+    "yuva444p",
+    "rgba",
+    "bgra",
+    "argb",
+    "abgr",
+    "ya8",
+];
+
 fn normalize_path(path: &str) -> String {
     path.replace('\\', "/")
 }
@@ -89,7 +512,7 @@ fn parse_fps(value: &str) -> f32 {
     value.parse::<f32>().unwrap_or(0.0)
 }
 
-fn build_output_path(input_path: &Path, ext: &str) -> Result<PathBuf, String> {
+fn build_output_path(input_path: &Path, ext: &str, output_kind: &str) -> Result<PathBuf, String> {
     let parent = input_path
         .parent()
         .ok_or_else(|| "入力ファイルの親フォルダを取得できません".to_string())?;
@@ -97,26 +520,58 @@ fn build_output_path(input_path: &Path, ext: &str) -> Result<PathBuf, String> {
         .file_stem()
         .and_then(|name| name.to_str())
         .ok_or_else(|| "入力ファイル名を解決できません".to_string())?;
-    Ok(parent.join(format!("{}_converted.{}", stem, ext.to_lowercase())))
+
+    match output_kind {
+        "hls" => Ok(parent.join(format!("{stem}_hls")).join("index.m3u8")),
+        "dash" => Ok(parent.join(format!("{stem}_dash")).join("manifest.mpd")),
+        _ => Ok(parent.join(format!("{}_converted.{}", stem, ext.to_lowercase()))),
+    }
 }
 
-fn build_ffmpeg_args(input_path: &Path, output_path: &Path, options: &ConvertOptions) -> Vec<String> {
+fn build_ffmpeg_args(
+    input_path: &Path,
+    output_path: &Path,
+    options: &ConvertOptions,
+    hdr: Option<&HdrMetadata>,
+) -> Vec<String> {
     let mut args = vec![
         "-y".to_string(),
         "-i".to_string(),
         input_path.display().to_string(),
+        "-progress".to_string(),
+        "pipe:2".to_string(),
+        "-nostats".to_string(),
         "-vf".to_string(),
         format!("scale={}:{}", options.width, options.height),
-        "-b:v".to_string(),
-        format!("{}k", options.video_bitrate_k),
-        "-crf".to_string(),
-        options.crf.to_string(),
-        "-c:a".to_string(),
-        options.audio_format.clone(),
-        "-b:a".to_string(),
-        format!("{}k", options.audio_bitrate_k),
     ];
 
+    args.push("-c:v".to_string());
+    args.push(options.video_codec.encoder_name().to_string());
+    args.extend(options.video_codec.rate_control_args(
+        &options.quality_mode,
+        options.crf,
+        options.video_bitrate_k,
+    ));
+
+    if let Some(hdr) = hdr {
+        args.push("-color_primaries".to_string());
+        args.push(hdr.color_primaries.clone());
+        args.push("-color_trc".to_string());
+        args.push(hdr.color_transfer.clone());
+        args.push("-colorspace".to_string());
+        args.push(hdr.color_space.clone());
+
+        if let Some(params) = hdr_encoder_params(options.video_codec, hdr) {
+            args.push(params.0);
+            args.push(params.1);
+        }
+    }
+
+    args.push("-c:a".to_string());
+    args.push(options.audio_codec.encoder_name().to_string());
+    args.push("-b:a".to_string());
+    args.push(format!("{}k", options.audio_bitrate_k));
+
     if options.fps_mode == "fixed" {
         args.push("-r".to_string());
         args.push(options.frame_rate.to_string());
@@ -127,10 +582,55 @@ fn build_ffmpeg_args(input_path: &Path, output_path: &Path, options: &ConvertOpt
         args.push("vfr".to_string());
     }
 
+    let output_dir = output_path.parent().unwrap_or_else(|| Path::new("."));
+    match options.output_kind.as_str() {
+        "hls" => {
+            args.push("-f".to_string());
+            args.push("hls".to_string());
+            args.push("-hls_time".to_string());
+            args.push(HLS_SEGMENT_SEC.to_string());
+            args.push("-hls_playlist_type".to_string());
+            args.push("vod".to_string());
+            args.push("-hls_segment_filename".to_string());
+            args.push(output_dir.join("segment_%03d.ts").display().to_string());
+        }
+        "dash" => {
+            args.push("-f".to_string());
+            args.push("dash".to_string());
+            args.push("-use_timeline".to_string());
+            args.push("1".to_string());
+            args.push("-use_template".to_string());
+            args.push("1".to_string());
+            args.push("-seg_duration".to_string());
+            args.push(DASH_SEGMENT_SEC.to_string());
+            args.push("-adaptation_sets".to_string());
+            args.push("id=0,streams=v id=1,streams=a".to_string());
+        }
+        _ => {}
+    }
+
     args.push(output_path.display().to_string());
     args
 }
 
+fn list_generated_segments(output_dir: &Path, manifest_path: &Path) -> Vec<String> {
+    let mut segments = Vec::new();
+    let Ok(entries) = fs::read_dir(output_dir) else {
+        return segments;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == manifest_path {
+            continue;
+        }
+        if path.is_file() {
+            segments.push(normalize_path(path.to_string_lossy().as_ref()));
+        }
+    }
+    segments.sort();
+    segments
+}
+
 fn run_ffprobe(ffprobe_executable: &Path, input_path: &Path) -> Result<ProbeResult, String> {
     let output = Command::new(ffprobe_executable)
         .arg("-v")
@@ -165,6 +665,16 @@ fn run_ffprobe(ffprobe_executable: &Path, input_path: &Path) -> Result<ProbeResu
     let mut audio_bitrate_k = 0;
     let mut audio_format = "aac".to_string();
     let mut duration_sec = 0.0;
+    let mut video_codec_long_name = String::new();
+    let mut audio_codec_long_name = String::new();
+    let mut pixel_format = String::new();
+    let mut has_alpha = false;
+    let mut frame_count = 0;
+    let mut color_primaries = "unknown".to_string();
+    let mut color_transfer = "unknown".to_string();
+    let mut color_space = "unknown".to_string();
+    let mut mastering_display = None;
+    let mut content_light_level = None;
 
     for stream in streams {
         let codec_type = stream
@@ -186,6 +696,52 @@ fn run_ffprobe(ffprobe_executable: &Path, input_path: &Path) -> Result<ProbeResu
                 .and_then(|v| v.parse::<u64>().ok())
                 .map(|v| (v / 1000) as u32)
                 .unwrap_or(0);
+            video_codec_long_name = stream
+                .get("codec_long_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            pixel_format = stream
+                .get("pix_fmt")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            has_alpha = ALPHA_PIXEL_FORMATS.contains(&pixel_format.as_str());
+            frame_count = stream
+                .get("nb_frames")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0);
+            color_primaries = stream
+                .get("color_primaries")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            color_transfer = stream
+                .get("color_transfer")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            color_space = stream
+                .get("color_space")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+
+            if let Some(side_data_list) = stream.get("side_data_list").and_then(|v| v.as_array()) {
+                for side_data in side_data_list {
+                    let side_data_type = side_data
+                        .get("side_data_type")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default();
+                    if side_data_type == "Mastering display metadata" {
+                        mastering_display = Some(format_mastering_display(side_data));
+                    }
+                    if side_data_type == "Content light level metadata" {
+                        content_light_level = Some(format_content_light_level(side_data));
+                    }
+                }
+            }
         }
         if codec_type == "audio" {
             audio_bitrate_k = stream
@@ -199,11 +755,22 @@ fn run_ffprobe(ffprobe_executable: &Path, input_path: &Path) -> Result<ProbeResu
                 .and_then(|v| v.as_str())
                 .unwrap_or("aac")
                 .to_string();
+            audio_codec_long_name = stream
+                .get("codec_long_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
         }
     }
 
-    if let Some(value) = value
-        .get("format")
+    let format = value.get("format");
+    let format_name = format
+        .and_then(|v| v.get("format_name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    if let Some(value) = format
         .and_then(|v| v.get("duration"))
         .and_then(|v| v.as_str())
         .and_then(|v| v.parse::<f32>().ok())
@@ -211,6 +778,12 @@ fn run_ffprobe(ffprobe_executable: &Path, input_path: &Path) -> Result<ProbeResu
         duration_sec = value;
     }
 
+    if frame_count == 0 && duration_sec > 0.0 && frame_rate > 0.0 {
+        frame_count = (duration_sec * frame_rate).round() as u64;
+    }
+
+    let hdr = HDR_TRANSFER_CHARACTERISTICS.contains(&color_transfer.as_str());
+
     Ok(ProbeResult {
         width,
         height,
@@ -219,9 +792,589 @@ fn run_ffprobe(ffprobe_executable: &Path, input_path: &Path) -> Result<ProbeResu
         audio_bitrate_k,
         audio_format,
         duration_sec,
+        format_name,
+        video_codec_long_name,
+        audio_codec_long_name,
+        pixel_format,
+        has_alpha,
+        frame_count,
+        color_primaries,
+        color_transfer,
+        color_space,
+        hdr,
+        mastering_display,
+        content_light_level,
     })
 }
 
+/// ffprobeの "13250/50000" のような有理数文字列を評価し、浮動小数値にする。
+fn parse_rational(value: &str) -> f64 {
+    match value.split_once('/') {
+        Some((num, den)) => {
+            let num: f64 = num.trim().parse().unwrap_or(0.0);
+            let den: f64 = den.trim().parse().unwrap_or(1.0);
+            if den == 0.0 { 0.0 } else { num / den }
+        }
+        None => value.trim().parse().unwrap_or(0.0),
+    }
+}
+
+fn format_mastering_display(side_data: &serde_json::Value) -> String {
+    // x265/SVT-AV1のmaster-display構文は整数のみを受け付ける。
+    // 色度は1/50000単位、輝度は1/10000単位にスケールし直す。
+    let chroma = |key: &str| -> i64 {
+        let raw = side_data.get(key).and_then(|v| v.as_str()).unwrap_or("0/1");
+        (parse_rational(raw) * 50000.0).round() as i64
+    };
+    let luminance = |key: &str| -> i64 {
+        let raw = side_data.get(key).and_then(|v| v.as_str()).unwrap_or("0/1");
+        (parse_rational(raw) * 10000.0).round() as i64
+    };
+    format!(
+        "G({},{})B({},{})R({},{})WP({},{})L({},{})",
+        chroma("green_x"),
+        chroma("green_y"),
+        chroma("blue_x"),
+        chroma("blue_y"),
+        chroma("red_x"),
+        chroma("red_y"),
+        chroma("white_point_x"),
+        chroma("white_point_y"),
+        luminance("max_luminance"),
+        luminance("min_luminance"),
+    )
+}
+
+fn format_content_light_level(side_data: &serde_json::Value) -> String {
+    let max_content = side_data.get("max_content").and_then(|v| v.as_u64()).unwrap_or(0);
+    let max_average = side_data.get("max_average").and_then(|v| v.as_u64()).unwrap_or(0);
+    format!("{max_content},{max_average}")
+}
+
+const VMAF_PROBE_SAMPLE_SEC: f32 = 4.0;
+const VMAF_TOLERANCE: f64 = 0.5;
+const VMAF_MAX_ITERATIONS: u32 = 4;
+
+struct CrfProbePoint {
+    crf: u8,
+    vmaf: f64,
+}
+
+fn extract_vmaf_sample(
+    ffmpeg_executable: &Path,
+    input_path: &Path,
+    start_sec: f32,
+    width: u32,
+    height: u32,
+    sample_dir: &Path,
+) -> Result<PathBuf, String> {
+    // 実エンコードと同じ解像度にスケールしたものを基準にしないと、
+    // ダウンスケール出力に対するVMAF計測とCRF選定がずれる。
+    let sample_path = sample_dir.join("reference.mkv");
+    let status = Command::new(ffmpeg_executable)
+        .arg("-y")
+        .arg("-ss")
+        .arg(format!("{start_sec}"))
+        .arg("-i")
+        .arg(input_path)
+        .arg("-t")
+        .arg(format!("{VMAF_PROBE_SAMPLE_SEC}"))
+        .arg("-an")
+        .arg("-vf")
+        .arg(format!("scale={width}:{height}"))
+        .arg("-c:v")
+        .arg("libx264")
+        .arg("-crf")
+        .arg("0")
+        .arg("-preset")
+        .arg("ultrafast")
+        .arg(&sample_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|error| format!("ERR_VMAF_SAMPLE: サンプル抽出起動失敗: {error}"))?;
+
+    if !status.success() {
+        return Err(format!("ERR_VMAF_SAMPLE: サンプル抽出失敗: status={status}"));
+    }
+    Ok(sample_path)
+}
+
+fn encode_vmaf_probe(
+    ffmpeg_executable: &Path,
+    sample_path: &Path,
+    video_codec: VideoCodec,
+    crf: u8,
+    probe_dir: &Path,
+) -> Result<PathBuf, String> {
+    let probe_path = probe_dir.join(format!("probe_crf{crf}.mkv"));
+    let mut command = Command::new(ffmpeg_executable);
+    command
+        .arg("-y")
+        .arg("-i")
+        .arg(sample_path)
+        .arg("-c:v")
+        .arg(video_codec.encoder_name())
+        .args(video_codec.rate_control_args("crf", crf, 0));
+    match video_codec {
+        VideoCodec::H264 | VideoCodec::H265 => {
+            command.arg("-preset").arg("medium");
+        }
+        VideoCodec::Vp9 => {
+            command.arg("-deadline").arg("good").arg("-cpu-used").arg("4");
+        }
+        VideoCodec::Av1 => {}
+    }
+    let status = command
+        .arg(&probe_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|error| format!("ERR_VMAF_PROBE: プローブエンコード起動失敗: {error}"))?;
+
+    if !status.success() {
+        return Err(format!("ERR_VMAF_PROBE: プローブエンコード失敗: status={status}"));
+    }
+    Ok(probe_path)
+}
+
+fn measure_vmaf(
+    ffmpeg_executable: &Path,
+    probe_path: &Path,
+    reference_path: &Path,
+    probe_dir: &Path,
+) -> Result<f64, String> {
+    let log_path = probe_dir.join("vmaf.json");
+    let log_path_arg = normalize_path(log_path.to_string_lossy().as_ref());
+    let status = Command::new(ffmpeg_executable)
+        .arg("-i")
+        .arg(probe_path)
+        .arg("-i")
+        .arg(reference_path)
+        .arg("-lavfi")
+        .arg(format!(
+            "[0:v][1:v]libvmaf=log_fmt=json:log_path={log_path_arg}"
+        ))
+        .arg("-f")
+        .arg("null")
+        .arg("-")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|error| format!("ERR_VMAF_MEASURE: VMAF計測起動失敗: {error}"))?;
+
+    if !status.success() {
+        return Err(format!("ERR_VMAF_MEASURE: VMAF計測失敗: status={status}"));
+    }
+
+    parse_vmaf_json(&log_path)
+}
+
+fn parse_vmaf_json(log_path: &Path) -> Result<f64, String> {
+    let text = fs::read_to_string(log_path)
+        .map_err(|error| format!("ERR_VMAF_MEASURE: VMAFログ読み取り失敗: {error}"))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|error| format!("ERR_VMAF_MEASURE: VMAF JSON解析失敗: {error}"))?;
+
+    value
+        .get("pooled_metrics")
+        .and_then(|metrics| metrics.get("vmaf"))
+        .and_then(|vmaf| vmaf.get("mean"))
+        .and_then(|mean| mean.as_f64())
+        .ok_or_else(|| "ERR_VMAF_MEASURE: VMAFスコアが取得できません".to_string())
+}
+
+/// target_vmafを挟む2点のうち、最もVMAF幅が狭い(=直線近似が最も正確な)組を選ぶ。
+/// 挟む組が無い場合はtarget_vmafに最も近い2点にフォールバックする。
+fn select_bracket(points: &[CrfProbePoint], target_vmaf: f64) -> (&CrfProbePoint, &CrfProbePoint) {
+    let mut best: Option<(&CrfProbePoint, &CrfProbePoint, f64)> = None;
+    for i in 0..points.len() {
+        for j in (i + 1)..points.len() {
+            let (a, b) = (&points[i], &points[j]);
+            let (low, high) = if a.vmaf <= b.vmaf { (a, b) } else { (b, a) };
+            if low.vmaf <= target_vmaf && target_vmaf <= high.vmaf {
+                let width = high.vmaf - low.vmaf;
+                if best.map(|(_, _, best_width)| width < best_width).unwrap_or(true) {
+                    best = Some((low, high, width));
+                }
+            }
+        }
+    }
+    if let Some((low, high, _)) = best {
+        return (low, high);
+    }
+
+    let mut by_distance: Vec<&CrfProbePoint> = points.iter().collect();
+    by_distance.sort_by(|a, b| {
+        (a.vmaf - target_vmaf)
+            .abs()
+            .partial_cmp(&(b.vmaf - target_vmaf).abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    (by_distance[0], by_distance[1])
+}
+
+fn interpolate_crf(points: &[CrfProbePoint], target_vmaf: f64, crf_range: (u8, u8)) -> u8 {
+    let (low, high) = select_bracket(points, target_vmaf);
+    if (high.vmaf - low.vmaf).abs() < f64::EPSILON {
+        return low.crf;
+    }
+    let slope = (high.crf as f64 - low.crf as f64) / (high.vmaf - low.vmaf);
+    let predicted = low.crf as f64 + slope * (target_vmaf - low.vmaf);
+    predicted.round().clamp(crf_range.0 as f64, crf_range.1 as f64) as u8
+}
+
+fn find_crf_for_quality_internal(
+    ffmpeg_executable: &Path,
+    input_path: &Path,
+    duration_sec: f32,
+    target_vmaf: f32,
+    video_codec: VideoCodec,
+    width: u32,
+    height: u32,
+) -> Result<u8, String> {
+    let probe_dir = input_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(format!(
+            ".vmaf-probe-{}",
+            input_path.file_stem().and_then(|s| s.to_str()).unwrap_or("input")
+        ));
+    fs::create_dir_all(&probe_dir).map_err(|error| format!("ERR_VMAF_PROBE: 作業フォルダ作成失敗: {error}"))?;
+
+    let cleanup = |dir: &Path| {
+        let _ = fs::remove_dir_all(dir);
+    };
+
+    let crf_range = video_codec.vmaf_probe_crf_range();
+    let start_sec = (duration_sec / 2.0 - VMAF_PROBE_SAMPLE_SEC / 2.0).max(0.0);
+    let result = (|| -> Result<u8, String> {
+        let sample_path = extract_vmaf_sample(ffmpeg_executable, input_path, start_sec, width, height, &probe_dir)?;
+
+        let mut points = Vec::new();
+        for crf in [crf_range.1, crf_range.0] {
+            let probe_path = encode_vmaf_probe(ffmpeg_executable, &sample_path, video_codec, crf, &probe_dir)?;
+            let vmaf = measure_vmaf(ffmpeg_executable, &probe_path, &sample_path, &probe_dir)?;
+            points.push(CrfProbePoint { crf, vmaf });
+        }
+
+        let mut candidate_crf = interpolate_crf(&points, target_vmaf as f64, crf_range);
+        for _ in 0..VMAF_MAX_ITERATIONS {
+            let probe_path =
+                encode_vmaf_probe(ffmpeg_executable, &sample_path, video_codec, candidate_crf, &probe_dir)?;
+            let measured_vmaf = measure_vmaf(ffmpeg_executable, &probe_path, &sample_path, &probe_dir)?;
+            if (measured_vmaf - target_vmaf as f64).abs() <= VMAF_TOLERANCE {
+                return Ok(candidate_crf);
+            }
+            points.push(CrfProbePoint {
+                crf: candidate_crf,
+                vmaf: measured_vmaf,
+            });
+            candidate_crf = interpolate_crf(&points, target_vmaf as f64, crf_range);
+        }
+
+        Ok(candidate_crf)
+    })();
+
+    cleanup(&probe_dir);
+    result
+}
+
+const SCENE_CHANGE_THRESHOLD: f32 = 0.3;
+const MIN_CHUNK_SEC: f32 = 5.0;
+
+fn detect_scene_boundaries(ffmpeg_executable: &Path, input_path: &Path) -> Result<Vec<f32>, String> {
+    let output = Command::new(ffmpeg_executable)
+        .arg("-i")
+        .arg(input_path)
+        .arg("-vf")
+        .arg(format!(
+            "select='gt(scene,{SCENE_CHANGE_THRESHOLD})',showinfo"
+        ))
+        .arg("-f")
+        .arg("null")
+        .arg("-")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|error| format!("ERR_SCENE_DETECT: シーン検出起動失敗: {error}"))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut boundaries = Vec::new();
+    for line in stderr.lines() {
+        if !line.contains("pts_time:") {
+            continue;
+        }
+        if let Some(time) = line
+            .split("pts_time:")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|value| value.parse::<f32>().ok())
+        {
+            boundaries.push(time);
+        }
+    }
+    Ok(boundaries)
+}
+
+fn merge_scene_boundaries(boundaries: &[f32], duration_sec: f32, min_chunk_sec: f32) -> Vec<(f32, f32)> {
+    let mut cut_points: Vec<f32> = vec![0.0];
+    for &boundary in boundaries {
+        if boundary - cut_points.last().copied().unwrap_or(0.0) >= min_chunk_sec {
+            cut_points.push(boundary);
+        }
+    }
+
+    let mut chunks = Vec::new();
+    for window in cut_points.windows(2) {
+        chunks.push((window[0], window[1]));
+    }
+    let last_start = cut_points.last().copied().unwrap_or(0.0);
+    if duration_sec - last_start >= min_chunk_sec || chunks.is_empty() {
+        chunks.push((last_start, duration_sec));
+    } else if let Some(last_chunk) = chunks.last_mut() {
+        last_chunk.1 = duration_sec;
+    }
+    chunks
+}
+
+fn segment_source(
+    ffmpeg_executable: &Path,
+    input_path: &Path,
+    chunks: &[(f32, f32)],
+    chunk_dir: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    let mut chunk_paths = Vec::new();
+    for (index, (start, end)) in chunks.iter().enumerate() {
+        let chunk_path = chunk_dir.join(format!("chunk_{index:04}.mkv"));
+        // シーン検出境界は任意のフレームなので、-c copyのキーフレームスナップでは
+        // 境界がずれて結合時に重複/欠落が生じる。-ss/-toを-iの後に置いてデコードし、
+        // 可逆エンコードでフレーム精度の切り出しを行う。
+        let status = Command::new(ffmpeg_executable)
+            .arg("-y")
+            .arg("-i")
+            .arg(input_path)
+            .arg("-ss")
+            .arg(format!("{start}"))
+            .arg("-to")
+            .arg(format!("{end}"))
+            .arg("-c:v")
+            .arg("libx264")
+            .arg("-crf")
+            .arg("0")
+            .arg("-preset")
+            .arg("ultrafast")
+            .arg("-c:a")
+            .arg("pcm_s16le")
+            .arg(&chunk_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|error| format!("ERR_SEGMENT: チャンク分割起動失敗: {error}"))?;
+        if !status.success() {
+            return Err(format!("ERR_SEGMENT: チャンク分割失敗: index={index} status={status}"));
+        }
+        chunk_paths.push(chunk_path);
+    }
+    Ok(chunk_paths)
+}
+
+fn encode_chunk(
+    ffmpeg_executable: &Path,
+    chunk_path: &Path,
+    output_path: &Path,
+    options: &ConvertOptions,
+    hdr: Option<&HdrMetadata>,
+    state: &AppState,
+    frame_counter: &AtomicU64,
+    app: &tauri::AppHandle,
+    total_frames: f32,
+) -> Result<(), String> {
+    let args = build_ffmpeg_args(chunk_path, output_path, options, hdr);
+    let mut child = Command::new(ffmpeg_executable)
+        .args(&args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("ERR_START: チャンクエンコード起動失敗: {error}"))?;
+
+    {
+        let mut running = state
+            .current_pid
+            .lock()
+            .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
+        running.push(child.id());
+    }
+
+    let execution_result: Result<(), String> = (|| {
+        if let Some(stderr) = child.stderr.take() {
+            let reader = BufReader::new(stderr);
+            let mut progress = ProgressAccumulator::default();
+            let mut reported_frame = 0u64;
+            for line in reader.lines() {
+                let text = line.map_err(|error| format!("ERR_LOG: ログ読み取り失敗: {error}"))?;
+                let trimmed = text.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if let Some((key, _)) = trimmed.split_once('=') {
+                    if progress.apply_line(trimmed).is_some() || key.trim() == "progress" {
+                        let delta = progress.frame.saturating_sub(reported_frame);
+                        if delta > 0 {
+                            let processed = frame_counter.fetch_add(delta, Ordering::SeqCst) + delta;
+                            reported_frame = progress.frame;
+                            // 全チャンクのフレーム数を合算し、チャンク完了を待たずに進捗を通知する。
+                            let percent = if total_frames > 0.0 {
+                                (processed as f32 / total_frames * 100.0).clamp(0.0, 100.0)
+                            } else {
+                                0.0
+                            };
+                            let _ = app.emit(
+                                "convert-progress",
+                                ConvertProgress {
+                                    out_time_sec: progress.out_time_sec,
+                                    frame: processed,
+                                    fps: progress.fps,
+                                    speed: progress.speed,
+                                    percent,
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let status = child
+            .wait()
+            .map_err(|error| format!("ERR_WAIT: チャンク待機失敗: {error}"))?;
+        if !status.success() {
+            return Err(format!("ERR_CONVERT: チャンクエンコード失敗: status={status}"));
+        }
+        Ok(())
+    })();
+
+    {
+        let mut running = state
+            .current_pid
+            .lock()
+            .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
+        running.retain(|pid| *pid != child.id());
+    }
+
+    execution_result
+}
+
+fn write_concat_list(chunk_outputs: &[PathBuf], list_path: &Path) -> Result<(), String> {
+    let mut list_file =
+        File::create(list_path).map_err(|error| format!("ERR_CONCAT: concatリスト作成失敗: {error}"))?;
+    for chunk_output in chunk_outputs {
+        let escaped = chunk_output.to_string_lossy().replace('\'', "'\\''");
+        writeln!(list_file, "file '{escaped}'")
+            .map_err(|error| format!("ERR_CONCAT: concatリスト書き込み失敗: {error}"))?;
+    }
+    Ok(())
+}
+
+fn concat_chunks(ffmpeg_executable: &Path, list_path: &Path, output_path: &Path) -> Result<(), String> {
+    let status = Command::new(ffmpeg_executable)
+        .arg("-y")
+        .arg("-f")
+        .arg("concat")
+        .arg("-safe")
+        .arg("0")
+        .arg("-i")
+        .arg(list_path)
+        .arg("-c")
+        .arg("copy")
+        .arg(output_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|error| format!("ERR_CONCAT: concat起動失敗: {error}"))?;
+    if !status.success() {
+        return Err(format!("ERR_CONCAT: concat失敗: status={status}"));
+    }
+    Ok(())
+}
+
+fn encode_chunks_in_parallel(
+    ffmpeg_executable: &Path,
+    chunk_paths: Vec<PathBuf>,
+    chunk_dir: &Path,
+    options: &ConvertOptions,
+    hdr: Option<&HdrMetadata>,
+    state: &tauri::State<'_, AppState>,
+    app: &tauri::AppHandle,
+    total_frames: f32,
+) -> Result<Vec<PathBuf>, String> {
+    let worker_count = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(chunk_paths.len().max(1));
+
+    let queue: Mutex<VecDeque<usize>> = Mutex::new((0..chunk_paths.len()).collect());
+    let results: Mutex<Vec<Option<PathBuf>>> = Mutex::new(vec![None; chunk_paths.len()]);
+    let first_error: Mutex<Option<String>> = Mutex::new(None);
+    let frame_counter = Arc::new(AtomicU64::new(0));
+
+    std::thread::scope(|scope| {
+        for _ in 0..worker_count {
+            let queue = &queue;
+            let results = &results;
+            let first_error = &first_error;
+            let chunk_paths = &chunk_paths;
+            let frame_counter = frame_counter.clone();
+            scope.spawn(move || loop {
+                let next_index = {
+                    let mut queue = queue.lock().unwrap();
+                    queue.pop_front()
+                };
+                let Some(index) = next_index else {
+                    break;
+                };
+                if first_error.lock().unwrap().is_some() {
+                    break;
+                }
+
+                let chunk_output = chunk_dir.join(format!("encoded_{index:04}.{}", options.output_ext));
+                let encode_result = encode_chunk(
+                    ffmpeg_executable,
+                    &chunk_paths[index],
+                    &chunk_output,
+                    options,
+                    hdr,
+                    state,
+                    &frame_counter,
+                    app,
+                    total_frames,
+                );
+
+                match encode_result {
+                    Ok(()) => {
+                        results.lock().unwrap()[index] = Some(chunk_output);
+                    }
+                    Err(error) => {
+                        *first_error.lock().unwrap() = Some(error);
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(error) = first_error.into_inner().unwrap() {
+        return Err(error);
+    }
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(index, path)| path.ok_or_else(|| format!("ERR_CONVERT: チャンク{index}の結果が欠落しています")))
+        .collect()
+}
+
 fn find_ffmpeg_on_path() -> Option<PathBuf> {
     let output = Command::new("ffmpeg").arg("-version").output().ok()?;
     if output.status.success() {
@@ -426,6 +1579,36 @@ async fn probe_video(app: tauri::AppHandle, input_path: String) -> Result<ProbeR
     run_ffprobe(&ffprobe, &input)
 }
 
+#[tauri::command]
+async fn find_crf_for_quality(
+    app: tauri::AppHandle,
+    input_path: String,
+    target_vmaf: f32,
+    video_codec: VideoCodec,
+    width: u32,
+    height: u32,
+) -> Result<u8, String> {
+    let input = PathBuf::from(input_path);
+    if !input.exists() {
+        return Err("入力ファイルが存在しません".to_string());
+    }
+    let ffmpeg_executable = {
+        let ready = ensure_ffmpeg_internal(&app).await?;
+        resolve_ffmpeg_executable_path(&app, &ready.ffmpeg_path)?
+    };
+    let ffprobe_executable = resolve_ffprobe_path(&app)?;
+    let duration_sec = run_ffprobe(&ffprobe_executable, &input)?.duration_sec;
+    find_crf_for_quality_internal(
+        &ffmpeg_executable,
+        &input,
+        duration_sec,
+        target_vmaf,
+        video_codec,
+        width,
+        height,
+    )
+}
+
 #[tauri::command]
 async fn preview_convert_command(
     input_path: String,
@@ -435,8 +1618,9 @@ async fn preview_convert_command(
     if !input.exists() {
         return Err("入力ファイルが存在しません".to_string());
     }
-    let output = build_output_path(&input, &options.output_ext)?;
-    let args = build_ffmpeg_args(&input, &output, &options);
+    validate_codec_container(&options.output_ext, options.video_codec, options.audio_codec)?;
+    let output = build_output_path(&input, &options.output_ext, &options.output_kind)?;
+    let args = build_ffmpeg_args(&input, &output, &options, None);
     Ok(ConvertPreview {
         output_path: normalize_path(output.to_string_lossy().as_ref()),
         args,
@@ -448,24 +1632,62 @@ async fn run_convert(
     app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     input_path: String,
-    options: ConvertOptions,
+    mut options: ConvertOptions,
+    input_limits: Option<InputLimits>,
 ) -> Result<ConvertResult, String> {
     let input = PathBuf::from(input_path);
     if !input.exists() {
         return Err("入力ファイルが存在しません".to_string());
     }
 
+    let warning = validate_codec_container(&options.output_ext, options.video_codec, options.audio_codec)?;
+
     let ready = ensure_ffmpeg_internal(&app).await?;
     let ffmpeg_executable = resolve_ffmpeg_executable_path(&app, &ready.ffmpeg_path)?;
-    let output = build_output_path(&input, &options.output_ext)?;
-    let args = build_ffmpeg_args(&input, &output, &options);
+    let ffprobe_executable = resolve_ffprobe_path(&app)?;
+    let probe = run_ffprobe(&ffprobe_executable, &input)?;
+    let duration_sec = probe.duration_sec;
+    let file_size_bytes = fs::metadata(&input)
+        .map_err(|error| format!("入力ファイル情報取得失敗: {error}"))?
+        .len();
+    check_input_limits(&probe, file_size_bytes, &input_limits.unwrap_or_default())?;
+
+    if options.quality_mode == "target_vmaf" {
+        let resolved_crf = find_crf_for_quality_internal(
+            &ffmpeg_executable,
+            &input,
+            duration_sec,
+            options.target_vmaf,
+            options.video_codec,
+            options.width,
+            options.height,
+        )?;
+        app.emit(
+            "convert-log",
+            serde_json::json!({ "message": format!("target_vmaf={} -> CRF={resolved_crf}", options.target_vmaf) }),
+        )
+        .map_err(|error| format!("ログ送信失敗: {error}"))?;
+        options.crf = resolved_crf;
+    }
+
+    let output = build_output_path(&input, &options.output_ext, &options.output_kind)?;
+    if let Some(output_dir) = output.parent() {
+        fs::create_dir_all(output_dir).map_err(|error| format!("出力フォルダ作成失敗: {error}"))?;
+    }
+    let hdr_metadata = HdrMetadata::from_probe(&probe);
+    let args = build_ffmpeg_args(&input, &output, &options, hdr_metadata.as_ref());
+
+    if let Some(message) = warning {
+        app.emit("convert-log", serde_json::json!({ "message": message }))
+            .map_err(|error| format!("ログ送信失敗: {error}"))?;
+    }
 
     {
         let running = state
             .current_pid
             .lock()
             .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
-        if running.is_some() {
+        if !running.is_empty() {
             return Err("ERR_BUSY: すでに変換中です".to_string());
         }
     }
@@ -492,14 +1714,29 @@ async fn run_convert(
             .current_pid
             .lock()
             .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
-        *running = Some(child.id());
+        running.push(child.id());
     }
     let execution_result: Result<ConvertResult, String> = (|| {
         if let Some(stderr) = child.stderr.take() {
             let reader = BufReader::new(stderr);
+            let mut progress = ProgressAccumulator::default();
             for line in reader.lines() {
                 match line {
                     Ok(text) if !text.trim().is_empty() => {
+                        let trimmed = text.trim();
+                        if let Some((key, value)) = trimmed.split_once('=') {
+                            if key.trim() == "progress" {
+                                app.emit("convert-progress", progress.to_progress(duration_sec))
+                                    .map_err(|error| format!("ERR_LOG: 進捗送信失敗: {error}"))?;
+                                if value.trim() == "end" {
+                                    progress = ProgressAccumulator::default();
+                                }
+                                continue;
+                            }
+                            if progress.apply_line(trimmed).is_some() {
+                                continue;
+                            }
+                        }
                         app.emit("convert-log", serde_json::json!({ "message": text }))
                             .map_err(|error| format!("ERR_LOG: ログ送信失敗: {error}"))?;
                     }
@@ -531,9 +1768,21 @@ async fn run_convert(
         )
         .map_err(|error| format!("ERR_LOG: ログ送信失敗: {error}"))?;
 
+        let (manifest_path, segments) = if options.output_kind == "file" {
+            (None, Vec::new())
+        } else {
+            let output_dir = output.parent().unwrap_or_else(|| Path::new("."));
+            (
+                Some(normalize_path(output.to_string_lossy().as_ref())),
+                list_generated_segments(output_dir, &output),
+            )
+        };
+
         Ok(ConvertResult {
             output_path: normalize_path(output.to_string_lossy().as_ref()),
             exit_code,
+            manifest_path,
+            segments,
         })
     })();
 
@@ -542,7 +1791,7 @@ async fn run_convert(
             .current_pid
             .lock()
             .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
-        *running = None;
+        running.clear();
     }
 
     execution_result
@@ -550,20 +1799,22 @@ async fn run_convert(
 
 #[tauri::command]
 async fn cancel_convert(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<CancelResult, String> {
-    let pid = {
-        let running = state
+    let pids = {
+        let mut running = state
             .current_pid
             .lock()
             .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
-        *running
+        std::mem::take(&mut *running)
     };
 
-    if let Some(pid) = pid {
+    if !pids.is_empty() {
         state.cancel_requested.store(true, Ordering::SeqCst);
-        kill_process_by_pid(pid)?;
+        for pid in &pids {
+            kill_process_by_pid(*pid)?;
+        }
         app.emit(
             "convert-log",
-            serde_json::json!({ "message": format!("キャンセル要求送信: pid={pid}") }),
+            serde_json::json!({ "message": format!("キャンセル要求送信: pid={:?}", pids) }),
         )
         .map_err(|error| format!("ERR_LOG: ログ送信失敗: {error}"))?;
         return Ok(CancelResult { requested: true });
@@ -572,18 +1823,131 @@ async fn cancel_convert(app: tauri::AppHandle, state: tauri::State<'_, AppState>
     Ok(CancelResult { requested: false })
 }
 
+#[tauri::command]
+async fn run_convert_parallel(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    input_path: String,
+    options: ConvertOptions,
+    input_limits: Option<InputLimits>,
+) -> Result<ConvertResult, String> {
+    let input = PathBuf::from(input_path);
+    if !input.exists() {
+        return Err("入力ファイルが存在しません".to_string());
+    }
+    if options.output_kind != "file" {
+        return Err("ERR_OUTPUT_KIND: 並列チャンクエンコードはfile出力のみ対応しています".to_string());
+    }
+    validate_codec_container(&options.output_ext, options.video_codec, options.audio_codec)?;
+
+    let ready = ensure_ffmpeg_internal(&app).await?;
+    let ffmpeg_executable = resolve_ffmpeg_executable_path(&app, &ready.ffmpeg_path)?;
+    let ffprobe_executable = resolve_ffprobe_path(&app)?;
+    let probe = run_ffprobe(&ffprobe_executable, &input)?;
+    let file_size_bytes = fs::metadata(&input)
+        .map_err(|error| format!("入力ファイル情報取得失敗: {error}"))?
+        .len();
+    check_input_limits(&probe, file_size_bytes, &input_limits.unwrap_or_default())?;
+    let output = build_output_path(&input, &options.output_ext, &options.output_kind)?;
+
+    {
+        let running = state
+            .current_pid
+            .lock()
+            .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
+        if !running.is_empty() {
+            return Err("ERR_BUSY: すでに変換中です".to_string());
+        }
+    }
+    state.cancel_requested.store(false, Ordering::SeqCst);
+
+    let chunk_dir = input
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(format!(
+            ".chunks-{}",
+            input.file_stem().and_then(|s| s.to_str()).unwrap_or("input")
+        ));
+    fs::create_dir_all(&chunk_dir).map_err(|error| format!("ERR_SEGMENT: 作業フォルダ作成失敗: {error}"))?;
+
+    let result: Result<ConvertResult, String> = (|| {
+        app.emit(
+            "convert-log",
+            serde_json::json!({ "message": "シーン検出を開始します" }),
+        )
+        .map_err(|error| format!("ERR_LOG: ログ送信失敗: {error}"))?;
+
+        let boundaries = detect_scene_boundaries(&ffmpeg_executable, &input)?;
+        let chunks = merge_scene_boundaries(&boundaries, probe.duration_sec, MIN_CHUNK_SEC);
+
+        app.emit(
+            "convert-log",
+            serde_json::json!({ "message": format!("{}個のチャンクに分割します", chunks.len()) }),
+        )
+        .map_err(|error| format!("ERR_LOG: ログ送信失敗: {error}"))?;
+
+        let chunk_paths = segment_source(&ffmpeg_executable, &input, &chunks, &chunk_dir)?;
+        let total_frames = probe.duration_sec * probe.frame_rate;
+        let hdr_metadata = HdrMetadata::from_probe(&probe);
+        let encoded_paths = encode_chunks_in_parallel(
+            &ffmpeg_executable,
+            chunk_paths,
+            &chunk_dir,
+            &options,
+            hdr_metadata.as_ref(),
+            &state,
+            &app,
+            total_frames,
+        )?;
+
+        if state.cancel_requested.load(Ordering::SeqCst) {
+            return Err("ERR_CANCELLED: 変換はユーザーによりキャンセルされました".to_string());
+        }
+
+        let list_path = chunk_dir.join("concat_list.txt");
+        write_concat_list(&encoded_paths, &list_path)?;
+        concat_chunks(&ffmpeg_executable, &list_path, &output)?;
+
+        app.emit(
+            "convert-log",
+            serde_json::json!({ "message": format!("変換成功: {}", output.display()) }),
+        )
+        .map_err(|error| format!("ERR_LOG: ログ送信失敗: {error}"))?;
+
+        Ok(ConvertResult {
+            output_path: normalize_path(output.to_string_lossy().as_ref()),
+            exit_code: 0,
+            manifest_path: None,
+            segments: Vec::new(),
+        })
+    })();
+
+    {
+        let mut running = state
+            .current_pid
+            .lock()
+            .map_err(|_| "ERR_STATE: state lock失敗".to_string())?;
+        running.clear();
+    }
+    let _ = fs::remove_dir_all(&chunk_dir);
+
+    result
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .manage(AppState {
-            current_pid: Mutex::new(None),
+            current_pid: Mutex::new(Vec::new()),
             cancel_requested: AtomicBool::new(false),
         })
         .invoke_handler(tauri::generate_handler![
             ensure_ffmpeg_ready,
             probe_video,
+            find_crf_for_quality,
             preview_convert_command,
             run_convert,
+            run_convert_parallel,
             cancel_convert
         ])
         .run(tauri::generate_context!())